@@ -0,0 +1,50 @@
+// Unless explicitly stated otherwise all files in this repository are licensed
+// under the MIT/Apache-2.0 License, at your convenience
+//
+// This product includes software developed at Datadog (https://www.datadoghq.com/). Copyright 2021 Datadog, Inc.
+
+use anyhow::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+
+fn default_iterations() -> u32 {
+    1
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Config {
+    pub name: Option<String>,
+    pub variant: Option<String>,
+    pub run: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default = "default_iterations")]
+    pub iterations: u32,
+    pub setup: Option<Vec<String>>,
+    #[serde(default)]
+    pub cachegrind: bool,
+    pub timeout: Option<u64>,
+    // Address the statsd listener binds to, e.g. "127.0.0.1:8125",
+    // "[::1]:0" for an ephemeral IPv6 port. Defaults to "127.0.0.1:8125"
+    // when unset, and can also be overridden with SIRUN_STATSD_LISTEN_ADDR.
+    pub statsd_addr: Option<String>,
+    // URL the final metrics document is POSTed to, in addition to stdout.
+    // Can also be set with SIRUN_PUSH_URL.
+    pub push_url: Option<String>,
+    // Extra headers (e.g. auth tokens) sent along with the push request.
+    // Can also be set with SIRUN_PUSH_HEADERS as a JSON object.
+    pub push_headers: Option<HashMap<String, String>>,
+    // How many times to retry a failed push before giving up. Defaults to 3.
+    // Can also be set with SIRUN_PUSH_RETRIES.
+    pub push_retries: Option<u32>,
+}
+
+pub fn get_config(path: &str) -> Result<Config> {
+    let mut file = File::open(path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    let config: Config = serde_yaml::from_str(&contents)?;
+    Ok(config)
+}
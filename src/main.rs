@@ -7,12 +7,17 @@ use anyhow::*;
 use async_std::{
     net::UdpSocket,
     process::{Command, Stdio},
+    stream::StreamExt,
     sync::{Arc, Barrier, RwLock},
     task::{sleep, spawn},
 };
 use serde::Serialize;
 use serde_json::json;
-use std::{collections::HashMap, env, process::exit, time::Duration};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook_async_std::Signals;
+use std::{
+    collections::HashMap, env, os::unix::process::CommandExt, process::exit, time::Duration,
+};
 
 mod config;
 use config::*;
@@ -25,6 +30,8 @@ use rusage::*;
 enum MetricValue {
     Str(String),
     Num(f64),
+    List(Vec<f64>),
+    Map(HashMap<String, MetricValue>),
     Arr(Vec<HashMap<String, MetricValue>>),
 }
 
@@ -56,12 +63,21 @@ num_type!(i32);
 num_type!(i64);
 num_type!(f64);
 
-async fn statsd_listener(barrier: Arc<Barrier>, statsd_buf: Arc<RwLock<String>>) -> Result<String> {
-    let socket = UdpSocket::bind("127.0.0.1:8125").await;
+// Binds to `listen_addr`, which may name an ephemeral port (`:0`) or an IPv6
+// literal. The actual bound address is published through `statsd_addr` before
+// the barrier is released, so `main` can pass it on to the child process.
+async fn statsd_listener(
+    barrier: Arc<Barrier>,
+    statsd_buf: Arc<RwLock<String>>,
+    statsd_addr: Arc<RwLock<String>>,
+    listen_addr: String,
+) -> Result<String> {
+    let socket = UdpSocket::bind(&listen_addr).await;
     let socket = match socket {
         Ok(s) => s,
-        Err(error) => panic!("Cannot bind to 127.0.0.1:8125: {}", error),
+        Err(error) => panic!("Cannot bind to {}: {}", listen_addr, error),
     };
+    *statsd_addr.write().await = socket.local_addr()?.to_string();
     barrier.wait().await; // indicates to main task that socket is listening
 
     loop {
@@ -73,18 +89,163 @@ async fn statsd_listener(barrier: Arc<Barrier>, statsd_buf: Arc<RwLock<String>>)
     }
 }
 
+// The metric type suffix of a DogStatsD line (`name:value|type|@rate|#tags`). A
+// missing suffix defaults to `Gauge`, matching the old behaviour of this parser.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StatsdType {
+    Counter,
+    Gauge,
+    Timer,
+    Histogram,
+    Set,
+    Distribution,
+}
+
+impl StatsdType {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "c" => Some(StatsdType::Counter),
+            "g" => Some(StatsdType::Gauge),
+            "ms" => Some(StatsdType::Timer),
+            "h" => Some(StatsdType::Histogram),
+            "s" => Some(StatsdType::Set),
+            "d" => Some(StatsdType::Distribution),
+            _ => None,
+        }
+    }
+}
+
+enum StatsdAggregate {
+    Counter(f64),
+    Gauge(f64),
+    Samples(Vec<f64>),
+    Set(std::collections::HashSet<String>),
+}
+
+// Tags are unordered on the wire, so sort them before using them as part of the
+// aggregation key; otherwise `#a:1,b:2` and `#b:2,a:1` would be treated as
+// distinct series.
+fn sorted_tags_key(raw_tags: &str) -> String {
+    let mut tags: Vec<&str> = raw_tags.split(',').filter(|tag| !tag.is_empty()).collect();
+    tags.sort_unstable();
+    tags.join(",")
+}
+
 fn get_statsd_metrics(metrics: &mut HashMap<String, MetricValue>, udp_data: String) -> Result<()> {
-    let lines = udp_data.trim().lines();
-    for line in lines {
-        let metric: Vec<&str> = match line.split('|').next() {
+    let mut aggregates: HashMap<(String, String), StatsdAggregate> = HashMap::new();
+
+    for line in udp_data.trim().lines() {
+        let mut fields = line.split('|');
+        let mut name_and_value = match fields.next() {
+            Some(name_and_value) if !name_and_value.is_empty() => name_and_value.splitn(2, ':'),
+            _ => continue,
+        };
+        let name = match name_and_value.next() {
+            Some(name) if !name.is_empty() => name.to_owned(),
+            _ => continue,
+        };
+        // Set members are arbitrary strings (e.g. user/request IDs), so the
+        // raw value can't be parsed as a number until we know the type isn't
+        // `s`. Every other type requires a numeric value.
+        let raw_value = match name_and_value.next() {
+            Some(raw_value) => raw_value,
             None => continue,
-            Some(metric) => metric.split(':').collect(),
         };
-        if metric.len() < 2 {
+
+        let mut metric_type = StatsdType::Gauge;
+        let mut rate: f64 = 1.0;
+        let mut tags = String::new();
+        for field in fields {
+            if let Some(parsed_type) = StatsdType::parse(field) {
+                metric_type = parsed_type;
+            } else if let Some(raw_rate) = field.strip_prefix('@') {
+                rate = raw_rate.parse().unwrap_or(1.0);
+            } else if let Some(raw_tags) = field.strip_prefix('#') {
+                tags = sorted_tags_key(raw_tags);
+            }
+        }
+
+        let key = (name, tags);
+        if metric_type == StatsdType::Set {
+            aggregates
+                .entry(key)
+                .and_modify(|aggregate| {
+                    if let StatsdAggregate::Set(seen) = aggregate {
+                        seen.insert(raw_value.to_owned());
+                    }
+                })
+                .or_insert_with(|| {
+                    let mut seen = std::collections::HashSet::new();
+                    seen.insert(raw_value.to_owned());
+                    StatsdAggregate::Set(seen)
+                });
             continue;
         }
-        metrics.insert(metric[0].into(), metric[1].parse::<f64>()?.into());
+
+        let value: f64 = match raw_value.parse() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        match metric_type {
+            StatsdType::Counter => {
+                let rate = if rate > 0.0 { rate } else { 1.0 };
+                let scaled = value / rate;
+                aggregates
+                    .entry(key)
+                    .and_modify(|aggregate| {
+                        if let StatsdAggregate::Counter(total) = aggregate {
+                            *total += scaled;
+                        }
+                    })
+                    .or_insert(StatsdAggregate::Counter(scaled));
+            }
+            StatsdType::Gauge => {
+                aggregates.insert(key, StatsdAggregate::Gauge(value));
+            }
+            StatsdType::Timer | StatsdType::Histogram | StatsdType::Distribution => {
+                aggregates
+                    .entry(key)
+                    .and_modify(|aggregate| {
+                        if let StatsdAggregate::Samples(samples) = aggregate {
+                            samples.push(value);
+                        }
+                    })
+                    .or_insert_with(|| StatsdAggregate::Samples(vec![value]));
+            }
+            StatsdType::Set => unreachable!("handled above"),
+        }
     }
+
+    let mut by_name: HashMap<String, Vec<(String, MetricValue)>> = HashMap::new();
+    for ((name, tags), aggregate) in aggregates {
+        let value = match aggregate {
+            StatsdAggregate::Counter(total) => MetricValue::Num(total),
+            StatsdAggregate::Gauge(last) => MetricValue::Num(last),
+            StatsdAggregate::Samples(samples) => MetricValue::List(samples),
+            StatsdAggregate::Set(seen) => MetricValue::Num(seen.len() as f64),
+        };
+        by_name.entry(name).or_default().push((tags, value));
+    }
+
+    for (name, mut series) in by_name {
+        if series.len() == 1 && series[0].0.is_empty() {
+            metrics.insert(name, series.pop().unwrap().1);
+        } else {
+            let by_tags = series
+                .into_iter()
+                .map(|(tags, value)| {
+                    let key = if tags.is_empty() {
+                        "untagged".to_owned()
+                    } else {
+                        tags
+                    };
+                    (key, value)
+                })
+                .collect();
+            metrics.insert(name, MetricValue::Map(by_tags));
+        }
+    }
+
     Ok(())
 }
 
@@ -131,32 +292,114 @@ async fn run_setup(setup: &[String], env: &HashMap<String, String>) -> Result<()
     Ok(())
 }
 
-async fn test_timeout(timeout: u64) {
+// POSTs the final metrics document to `url`, retrying with exponential
+// backoff on connection failure. Never fails the run: a push that keeps
+// failing is logged to stderr and swallowed, since stdout remains the
+// metrics document of record.
+async fn push_metrics(url: &str, headers: &HashMap<String, String>, retries: u32, body: &str) {
+    for attempt in 0..=retries {
+        let mut request = surf::post(url).body(body.to_owned());
+        for (name, value) in headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        match request.await {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => eprintln!(
+                "Pushing metrics to {} failed with status {}",
+                url,
+                response.status()
+            ),
+            Err(error) => eprintln!("Pushing metrics to {} failed: {}", url, error),
+        }
+        if attempt < retries {
+            sleep(Duration::from_secs(2u64.pow(attempt.min(5)))).await;
+        }
+    }
+    eprintln!(
+        "Giving up pushing metrics to {} after {} attempts.",
+        url,
+        retries + 1
+    );
+}
+
+// Forwards `signal` to the child's process group (if a child is currently
+// running), stops new statsd samples from accumulating, and prints whatever
+// was collected so far marked as partial before exiting with a code derived
+// from the signal, distinct from a normal run's exit codes.
+async fn graceful_shutdown(
+    signal: i32,
+    child_pid: Arc<RwLock<Option<i32>>>,
+    statsd_buf: Arc<RwLock<String>>,
+) -> ! {
+    if let Some(pid) = *child_pid.read().await {
+        unsafe {
+            libc::killpg(pid, signal);
+        }
+    }
+    // Holding the write lock until exit keeps the listener from appending
+    // any further samples to the buffer.
+    let statsd_buf = statsd_buf.write().await;
+
+    // Only the top-level process owns the single-JSON-line stdout contract;
+    // an iteration subprocess's stdout is inherited straight from its parent,
+    // so printing here would corrupt that document rather than replace it.
+    // The iteration's abort is instead propagated to the top-level process
+    // via its exit code (see the `status > 128` check in `run_iteration`),
+    // which prints the partial document itself.
+    if env::var("SIRUN_ITERATION").is_err() {
+        let mut metrics: HashMap<String, MetricValue> = HashMap::new();
+        metrics.insert("partial".into(), MetricValue::Num(1.0));
+        let _ = get_statsd_metrics(&mut metrics, (*statsd_buf).clone());
+        println!("{}", json!(metrics).to_string());
+    }
+    exit(128 + signal);
+}
+
+async fn signal_handler(
+    child_pid: Arc<RwLock<Option<i32>>>,
+    statsd_buf: Arc<RwLock<String>>,
+) -> Result<()> {
+    let mut signals = Signals::new([SIGINT, SIGTERM])?;
+    if let Some(signal) = signals.next().await {
+        graceful_shutdown(signal, child_pid, statsd_buf).await;
+    }
+    Ok(())
+}
+
+async fn test_timeout(
+    timeout: u64,
+    child_pid: Arc<RwLock<Option<i32>>>,
+    statsd_buf: Arc<RwLock<String>>,
+) {
     sleep(std::time::Duration::from_secs(timeout)).await;
     eprintln!("Timeout of {} seconds exceeded.", timeout);
-    exit(1);
+    graceful_shutdown(SIGTERM, child_pid, statsd_buf).await;
 }
 
 async fn run_test(
     config: &Config,
     mut metrics: &mut HashMap<String, MetricValue>,
     statsd_buf: Arc<RwLock<String>>,
+    child_pid: Arc<RwLock<Option<i32>>>,
 ) -> Result<()> {
     if let Some(timeout) = config.timeout {
-        spawn(test_timeout(timeout));
+        spawn(test_timeout(timeout, child_pid.clone(), statsd_buf.clone()));
     }
 
     let command = config.run[0].clone();
     let args = config.run.iter().skip(1);
     let start_time = std::time::Instant::now();
     let rusage_start = Rusage::new();
-    let status = Command::new(command)
+    let mut child = Command::new(command)
         .args(args)
         .envs(&config.env)
         .stdout(get_stdio())
         .stderr(get_stdio())
-        .status()
-        .await?;
+        .process_group(0) // own process group, so a signal can be forwarded to the whole tree
+        .spawn()?;
+    *child_pid.write().await = Some(child.id() as i32);
+    let status = child.status().await?;
+    *child_pid.write().await = None;
     let duration = start_time.elapsed().as_micros();
     let rusage_result = Rusage::new() - rusage_start;
     metrics.insert("wall.time".to_owned(), (duration as f64).into());
@@ -175,20 +418,31 @@ async fn run_iteration(
     config: &Config,
     mut metrics: &mut HashMap<String, MetricValue>,
     statsd_buf: Arc<RwLock<String>>,
+    child_pid: Arc<RwLock<Option<i32>>>,
 ) -> Result<()> {
     let mut config: Config = config.clone();
     let json_config = serde_yaml::to_string(&config)?;
     config.env.insert("SIRUN_ITERATION".into(), json_config);
     config.cachegrind = false;
     let command = env::args().next().unwrap();
-    let status = Command::new(command)
+    let mut child = Command::new(command)
         .envs(&config.env)
         .stdout(get_stdio())
         .stderr(get_stdio())
-        .status()
-        .await?;
+        .process_group(0)
+        .spawn()?;
+    *child_pid.write().await = Some(child.id() as i32);
+    let status = child.status().await?;
+    *child_pid.write().await = None;
     let status = status.code().expect("no exit code");
-    if status != 0 && status <= 128 {
+    if status > 128 {
+        // The iteration was itself killed by a signal or timeout: its own
+        // graceful_shutdown already forwarded the signal to its workload and
+        // exited with 128 + signal, without printing anything. Propagate that abort to this (top-level) process
+        // instead of silently recording an empty iteration and continuing.
+        graceful_shutdown(status - 128, child_pid, statsd_buf).await;
+    }
+    if status != 0 {
         exit(status);
     }
     get_statsd_metrics(&mut metrics, statsd_buf.read().await.clone())?;
@@ -196,10 +450,146 @@ async fn run_iteration(
     Ok(())
 }
 
+// Linear interpolation between the sorted sample's surrounding ranks, as
+// recommended by NIST: rank = p/100 * (n - 1).
+fn percentile(sorted_samples: &[f64], p: f64) -> f64 {
+    if sorted_samples.len() == 1 {
+        return sorted_samples[0];
+    }
+    let rank = p / 100.0 * (sorted_samples.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    if lower == upper {
+        sorted_samples[lower]
+    } else {
+        let weight = rank - lower as f64;
+        sorted_samples[lower] * (1.0 - weight) + sorted_samples[upper] * weight
+    }
+}
+
+// Summarizes every numeric metric that appears across iterations, so
+// consumers can compare runs without reimplementing basic statistics
+// themselves. Non-numeric metrics (e.g. name, variant, version) are absent
+// from iteration metrics already, so they never reach here.
+fn summarize_iterations(
+    iterations: &[HashMap<String, MetricValue>],
+) -> HashMap<String, MetricValue> {
+    let mut samples: HashMap<String, Vec<f64>> = HashMap::new();
+    for iteration in iterations {
+        for (name, value) in iteration {
+            match value {
+                // A bare numeric metric is one sample per iteration.
+                MetricValue::Num(sample) if sample.is_finite() => {
+                    samples.entry(name.clone()).or_default().push(*sample);
+                }
+                // Timer/histogram/distribution metrics carry every sample
+                // observed in the iteration; fold them all into the same
+                // pool so percentiles/stddev cover the full distribution
+                // rather than just one value per iteration.
+                MetricValue::List(list) => {
+                    samples
+                        .entry(name.clone())
+                        .or_default()
+                        .extend(list.iter().copied().filter(|sample| sample.is_finite()));
+                }
+                // Tagged metrics (MetricValue::Map) have one series per tag
+                // combination rather than a single value, so there's no
+                // well-defined flat sample to summarize here; they're left
+                // out of `summary` and only appear in the raw `iterations`.
+                _ => {}
+            }
+        }
+    }
+
+    let mut summary = HashMap::new();
+    for (name, mut values) in samples {
+        if values.is_empty() {
+            continue;
+        }
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let stddev = if values.len() > 1 {
+            (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+
+        let mut stats: HashMap<String, MetricValue> = HashMap::new();
+        stats.insert("min".into(), values[0].into());
+        stats.insert("max".into(), values[values.len() - 1].into());
+        stats.insert("mean".into(), mean.into());
+        stats.insert("median".into(), percentile(&values, 50.0).into());
+        stats.insert("stddev".into(), stddev.into());
+        stats.insert("p50".into(), percentile(&values, 50.0).into());
+        stats.insert("p90".into(), percentile(&values, 90.0).into());
+        stats.insert("p95".into(), percentile(&values, 95.0).into());
+        stats.insert("p99".into(), percentile(&values, 99.0).into());
+        summary.insert(name, MetricValue::Map(stats));
+    }
+
+    summary
+}
+
+// Strips everything that isn't a digit or a decimal point, so a token like
+// `19,063` or the `wr)` that follows it can both be handled uniformly.
+fn cachegrind_number(token: &str) -> Option<f64> {
+    let cleaned: String = token
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    if cleaned.is_empty() {
+        None
+    } else {
+        cleaned.parse().ok()
+    }
+}
+
+// Some lines are a plain total (`I1  misses:   3,893`) while others break the
+// total down into components in parentheses
+// (`Mispredicts:   97,923  ( 90,621 cond + 7,302 ind)`). The total always
+// precedes the opening paren when there is one, so prefer that token over
+// blindly taking the last one, which would land on part of the breakdown.
+fn cachegrind_total(line: &str) -> Option<f64> {
+    let tokens: Vec<&str> = line.trim().split_whitespace().collect();
+    match tokens.iter().position(|token| token.starts_with('(')) {
+        Some(paren_index) => paren_index
+            .checked_sub(1)
+            .and_then(|index| cachegrind_number(tokens[index])),
+        None => tokens.last().and_then(|token| cachegrind_number(token)),
+    }
+}
+
+// Cachegrind breaks some lines down into two components, e.g.
+// `D1  misses:    74,579  ( 55,516 rd  +  19,063 wr)` or
+// `Mispredicts:   97,923  ( 90,621 cond + 7,302 ind)`. Finds the numbers that
+// precede the two given labels, wherever they fall on the line.
+fn cachegrind_labeled_pair(
+    line: &str,
+    first_label: &str,
+    second_label: &str,
+) -> Option<(f64, f64)> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let mut first = None;
+    let mut second = None;
+    for (index, token) in tokens.iter().enumerate() {
+        let bare = token.trim_end_matches(')');
+        if index == 0 {
+            continue;
+        }
+        if bare == first_label {
+            first = cachegrind_number(tokens[index - 1]);
+        } else if bare == second_label {
+            second = cachegrind_number(tokens[index - 1]);
+        }
+    }
+    first.zip(second)
+}
+
 #[async_std::main]
 async fn main() -> Result<()> {
     let is_iteration = env::var("SIRUN_ITERATION").is_ok();
-    let config = if is_iteration {
+    let mut config: Config = if is_iteration {
         serde_yaml::from_str(&env::var("SIRUN_ITERATION").unwrap()).unwrap()
     } else {
         let config_file = env::args().nth(1).expect("missing file argument");
@@ -214,20 +604,48 @@ async fn main() -> Result<()> {
     let statsd_started = Arc::new(Barrier::new(2));
     let statsd_buf = Arc::new(RwLock::new(String::new()));
     if !is_iteration {
-        spawn(statsd_listener(statsd_started.clone(), statsd_buf.clone()));
+        let listen_addr = env::var("SIRUN_STATSD_LISTEN_ADDR")
+            .ok()
+            .or_else(|| config.statsd_addr.clone())
+            .unwrap_or_else(|| "127.0.0.1:8125".to_owned());
+        let statsd_addr = Arc::new(RwLock::new(String::new()));
+        spawn(statsd_listener(
+            statsd_started.clone(),
+            statsd_buf.clone(),
+            statsd_addr.clone(),
+            listen_addr,
+        ));
         statsd_started.wait().await; // waits for socket to be listening
+                                     // Let the test and every child iteration know where the listener
+                                     // actually ended up, since it may be an OS-assigned ephemeral port.
+        config
+            .env
+            .insert("SIRUN_STATSD_ADDR".into(), statsd_addr.read().await.clone());
     }
 
+    let child_pid: Arc<RwLock<Option<i32>>> = Arc::new(RwLock::new(None));
+    spawn(signal_handler(child_pid.clone(), statsd_buf.clone()));
+
     let mut metrics: HashMap<String, MetricValue> = HashMap::new();
     if is_iteration || config.iterations == 1 {
-        run_test(&config, &mut metrics, statsd_buf.clone()).await?;
+        run_test(&config, &mut metrics, statsd_buf.clone(), child_pid.clone()).await?;
     } else {
         let mut iterations = Vec::new();
         for _ in 0..config.iterations {
             let mut iteration_metrics = HashMap::new();
-            run_iteration(&config, &mut iteration_metrics, statsd_buf.clone()).await?;
+            run_iteration(
+                &config,
+                &mut iteration_metrics,
+                statsd_buf.clone(),
+                child_pid.clone(),
+            )
+            .await?;
             iterations.push(iteration_metrics);
         }
+        metrics.insert(
+            "summary".into(),
+            MetricValue::Map(summarize_iterations(&iterations)),
+        );
         metrics.insert("iterations".into(), MetricValue::Arr(iterations));
     }
 
@@ -240,8 +658,15 @@ async fn main() -> Result<()> {
             metrics.remove("wall.time").unwrap().as_f64(),
             metrics.remove("cpu.pct.wall.time").unwrap().as_f64()
         );
-        let sock = UdpSocket::bind("127.0.0.1:0").await?;
-        sock.send_to(buf.as_bytes(), "127.0.0.1:8125").await?;
+        let statsd_addr =
+            env::var("SIRUN_STATSD_ADDR").unwrap_or_else(|_| "127.0.0.1:8125".to_owned());
+        let bind_addr = if statsd_addr.starts_with('[') {
+            "[::]:0"
+        } else {
+            "0.0.0.0:0"
+        };
+        let sock = UdpSocket::bind(bind_addr).await?;
+        sock.send_to(buf.as_bytes(), &statsd_addr).await?;
     } else {
         if config.cachegrind {
             let command = "valgrind";
@@ -253,6 +678,7 @@ async fn main() -> Result<()> {
                 "--I1=32768,8,64".to_owned(),
                 "--D1=32768,8,64".to_owned(),
                 "--LL=8388608,16,64".to_owned(),
+                "--branch-sim=yes".to_owned(),
             ];
             args.append(&mut config.run.clone());
             let output = Command::new(command)
@@ -262,23 +688,77 @@ async fn main() -> Result<()> {
                 .await?;
             let stderr = String::from_utf8_lossy(&output.stderr);
 
-            let lines = stderr.trim().lines().filter(|x| x.contains("I   refs:"));
-            let mut instructions: f64 = 0.0;
-            for line in lines {
-                instructions += line
-                    .trim()
-                    .split_whitespace()
-                    .last()
-                    .expect("Bad cachegrind output: invalid instruction ref line")
-                    .replace(",", "")
-                    .parse::<f64>()
-                    .expect("Bad cachegrind output: invalid number");
-            }
+            let instructions = stderr
+                .trim()
+                .lines()
+                .filter(|line| line.contains("I   refs:"))
+                .filter_map(cachegrind_total)
+                .sum::<f64>();
             if instructions <= 0.0 {
                 eprintln!("Bad cachegrind output: no instructions parsed");
                 exit(1);
             }
             metrics.insert("instructions".into(), instructions.into());
+
+            let mut l1_misses = 0.0;
+            let mut ll_misses = 0.0;
+
+            if let Some(i1_miss) = stderr
+                .lines()
+                .find(|line| line.contains("I1  misses:"))
+                .and_then(cachegrind_total)
+            {
+                l1_misses += i1_miss;
+                metrics.insert("i1.miss".into(), i1_miss.into());
+            }
+            if let Some(lli_miss) = stderr
+                .lines()
+                .find(|line| line.contains("LLi misses:"))
+                .and_then(cachegrind_total)
+            {
+                ll_misses += lli_miss;
+                metrics.insert("lli.miss".into(), lli_miss.into());
+            }
+            if let Some((rd, wr)) = stderr
+                .lines()
+                .find(|line| line.contains("D1  misses:"))
+                .and_then(|line| cachegrind_labeled_pair(line, "rd", "wr"))
+            {
+                l1_misses += rd + wr;
+                metrics.insert("d1.miss.rd".into(), rd.into());
+                metrics.insert("d1.miss.wr".into(), wr.into());
+            }
+            if let Some((rd, wr)) = stderr
+                .lines()
+                .find(|line| line.contains("LLd misses:"))
+                .and_then(|line| cachegrind_labeled_pair(line, "rd", "wr"))
+            {
+                ll_misses += rd + wr;
+                metrics.insert("lld.miss.rd".into(), rd.into());
+                metrics.insert("lld.miss.wr".into(), wr.into());
+            }
+            if let Some(mispredicts) = stderr
+                .lines()
+                .find(|line| line.contains("Mispredicts:"))
+                .and_then(cachegrind_total)
+            {
+                metrics.insert("branch.mispred".into(), mispredicts.into());
+            }
+            if let Some((cond, ind)) = stderr
+                .lines()
+                .find(|line| line.contains("Mispredicts:"))
+                .and_then(|line| cachegrind_labeled_pair(line, "cond", "ind"))
+            {
+                metrics.insert("branch.mispred.cond".into(), cond.into());
+                metrics.insert("branch.mispred.ind".into(), ind.into());
+            }
+
+            // Standard cachegrind cost model: each L1 miss costs ~10 extra
+            // cycles, each last-level miss costs ~100.
+            metrics.insert(
+                "cycles.estimated".into(),
+                (instructions + 10.0 * l1_misses + 100.0 * ll_misses).into(),
+            );
         }
 
         if let Ok(hash) = env::var("GIT_COMMIT_HASH") {
@@ -291,7 +771,26 @@ async fn main() -> Result<()> {
             metrics.insert("variant".into(), variant.into());
         }
 
-        println!("{}", json!(metrics).to_string());
+        let document = json!(metrics).to_string();
+        println!("{}", document);
+
+        let push_url = env::var("SIRUN_PUSH_URL").ok().or(config.push_url);
+        if let Some(push_url) = push_url {
+            let push_retries = env::var("SIRUN_PUSH_RETRIES")
+                .ok()
+                .and_then(|retries| retries.parse().ok())
+                .or(config.push_retries)
+                .unwrap_or(3);
+            // SIRUN_PUSH_HEADERS mirrors push_headers as a JSON object, e.g.
+            // `{"Authorization": "Bearer ..."}`, for CI setups that'd rather
+            // not put an auth token in the checked-in config file.
+            let push_headers = env::var("SIRUN_PUSH_HEADERS")
+                .ok()
+                .and_then(|headers| serde_json::from_str::<HashMap<String, String>>(&headers).ok())
+                .or(config.push_headers)
+                .unwrap_or_default();
+            push_metrics(&push_url, &push_headers, push_retries, &document).await;
+        }
     }
 
     Ok(())
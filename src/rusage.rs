@@ -0,0 +1,39 @@
+// Unless explicitly stated otherwise all files in this repository are licensed
+// under the MIT/Apache-2.0 License, at your convenience
+//
+// This product includes software developed at Datadog (https://www.datadoghq.com/). Copyright 2021 Datadog, Inc.
+
+use std::ops::Sub;
+
+#[derive(Clone, Copy)]
+pub struct Rusage {
+    pub max_res_size: f64,
+    pub user_time: f64,
+    pub system_time: f64,
+}
+
+impl Rusage {
+    pub fn new() -> Self {
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        unsafe {
+            libc::getrusage(libc::RUSAGE_CHILDREN, &mut usage);
+        }
+        Rusage {
+            max_res_size: usage.ru_maxrss as f64,
+            user_time: usage.ru_utime.tv_sec as f64 + usage.ru_utime.tv_usec as f64 / 1_000_000.0,
+            system_time: usage.ru_stime.tv_sec as f64 + usage.ru_stime.tv_usec as f64 / 1_000_000.0,
+        }
+    }
+}
+
+impl Sub for Rusage {
+    type Output = Rusage;
+
+    fn sub(self, other: Rusage) -> Rusage {
+        Rusage {
+            max_res_size: self.max_res_size - other.max_res_size,
+            user_time: self.user_time - other.user_time,
+            system_time: self.system_time - other.system_time,
+        }
+    }
+}